@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+/// Which [`crate::Colour`] implementation backs the output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Palette {
+    /// The 8 named Discord/ANSI SGR colours, cycled.
+    Discord,
+    /// 24-bit ANSI escapes, generated to maximise hue/lightness separation.
+    Truecolor,
+    /// 256-colour ANSI escapes, cycling the 216-colour cube (indices 16-231);
+    /// the 16 system colours and the grey ramp are not used.
+    Ansi256,
+}
+
+/// Which document format the coloured output is rendered as.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// ANSI escape codes, for a terminal.
+    Text,
+    /// `<span>`-wrapped HTML, for embedding in a web viewer.
+    Html,
+    /// A JSON document exposing the isomorph groups directly.
+    Json,
+}
+
+/// How the input is split into the tokens that get checked for isomorphism.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Split {
+    /// Split on runs of whitespace (the default).
+    #[default]
+    Whitespace,
+    /// Treat every character as its own token.
+    Chars,
+    /// Split on newlines.
+    Lines,
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Highlight isomorphic tokens in text")]
+pub struct Args {
+    /// Colour backend used to render highlighted tokens.
+    #[arg(long, value_enum, default_value_t = Palette::Discord)]
+    pub palette: Palette,
+
+    /// Output document format. `html` and `json` ignore `--palette` in
+    /// favour of their own colour/grouping representation.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// How to tokenize the input before looking for isomorphisms.
+    #[arg(long, value_enum, default_value_t = Split::Whitespace)]
+    pub split: Split,
+
+    /// Explicit delimiter to split on, overriding `--split`.
+    #[arg(long)]
+    pub delimiter: Option<String>,
+
+    /// Only highlight isomorph classes with at least this many members.
+    #[arg(long, default_value_t = 2)]
+    pub min_group: usize,
+
+    /// Also require a highlighted class to contain a repeated symbol,
+    /// preserving the tool's original "must contain a duplicate letter"
+    /// behaviour on top of `--min-group`.
+    #[arg(long)]
+    pub require_repeat: bool,
+
+    /// Read input from this file instead of stdin.
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+
+    /// Scan overlapping windows of this many characters instead of tokenizing
+    /// the input, finding isomorphisms between windows rather than tokens.
+    #[arg(long)]
+    pub window: Option<usize>,
+}
+
+/// Splits `input` into tokens according to `args`, honouring an explicit
+/// `--delimiter` over `--split` when both are given.
+pub fn tokenize<'a>(input: &'a str, args: &Args) -> Vec<&'a str> {
+    if let Some(delimiter) = &args.delimiter {
+        return input.split(delimiter.as_str()).collect();
+    }
+
+    match args.split {
+        Split::Whitespace => input.split_whitespace().collect(),
+        Split::Chars => input
+            .char_indices()
+            .map(|(i, c)| &input[i..i + c.len_utf8()])
+            .collect(),
+        Split::Lines => input.lines().collect(),
+    }
+}
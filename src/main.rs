@@ -1,13 +1,23 @@
+mod cli;
+mod palette;
+mod render;
+mod window;
+
 use std::{
     collections::{HashMap, HashSet},
     error::Error,
+    fs,
     hash::Hash,
     io::{self, Read},
 };
 
+use clap::Parser;
+use cli::{Args, Palette};
+use render::OutputFormat;
+
 #[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
-struct ColourKey {
-    idx: usize,
+pub(crate) struct ColourKey {
+    pub(crate) idx: usize,
 }
 
 const RESET: &'static str = "\x1B[0m";
@@ -46,70 +56,80 @@ impl RGB {
 }
 
 #[derive(Copy, Clone)]
-pub struct DiscordColour {
+pub struct Ansi256Colour {
     value: u8,
 }
 
-impl Colour for DiscordColour {
+impl Colour for Ansi256Colour {
     fn ansify(&self) -> String {
-        "\x1B[".to_string() + &self.value.to_string() + "m"
+        "\x1B[38;5;".to_string() + &self.value.to_string() + "m"
     }
 }
 
-pub struct DiscordColourIterator {
+pub struct Ansi256ColourIterator {
     index: usize,
     total: usize,
 }
 
-impl DiscordColourIterator {
+impl Ansi256ColourIterator {
     pub fn new(total: usize) -> Self {
         Self { index: 0, total }
     }
 }
 
-impl Iterator for DiscordColourIterator {
-    type Item = DiscordColour;
+impl Iterator for Ansi256ColourIterator {
+    type Item = Ansi256Colour;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.total {
             return None;
         }
+
+        // Skip the first 16 (system) colours and cycle the 216-colour cube.
+        let value = 16 + (self.index % 216);
         self.index += 1;
 
-        let remainder = self.index % 8;
-        // TODO: consider bg combos
+        Some(Ansi256Colour { value: value as u8 })
+    }
+}
 
-        Some(DiscordColour {
-            value: remainder as u8 + 30,
-        })
+#[derive(Copy, Clone)]
+pub struct DiscordColour {
+    value: u8,
+}
+
+impl Colour for DiscordColour {
+    fn ansify(&self) -> String {
+        "\x1B[".to_string() + &self.value.to_string() + "m"
     }
 }
 
-pub struct RGBIterator {
+pub struct DiscordColourIterator {
     index: usize,
     total: usize,
 }
 
-impl RGBIterator {
+impl DiscordColourIterator {
     pub fn new(total: usize) -> Self {
         Self { index: 0, total }
     }
 }
 
-impl Iterator for RGBIterator {
-    type Item = RGB;
+impl Iterator for DiscordColourIterator {
+    type Item = DiscordColour;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.total {
             return None;
         }
-
-        let golden_angle = (1.0 + f64::sqrt(5.0)) * 60.0;
-        let hue = (self.index as f64 * golden_angle) % 360.0;
-
         self.index += 1;
 
-        Some(RGB::from_hsl(hue, 0.9, 0.6))
+        let remainder = self.index % 8;
+        // TODO: consider bg combos
+
+        Some(DiscordColour {
+            value: remainder as u8 + 30,
+        })
     }
 }
 
@@ -128,22 +148,22 @@ trait PossiblyIsomorphic: Clone + Eq + Hash {}
 impl<T: Clone + Eq + Hash> PossiblyIsomorphic for T {}
 
 /// An object is a valid response if it can do some basic stuff, this trait is effectively a trait alias
-trait Response: Clone {}
+pub(crate) trait Response: Clone {}
 impl<T: Clone> Response for T {}
 
 /// Like a [`String`], `R` (the response) is the value which is used to identify this if it was isomorphic
 /// For a string where you wanted to find isomorphisms in overlapping windows you might have `T` = [`char`] and `R` = [`std::ops::Range`]
-struct IsomorphicHolder<T: PossiblyIsomorphic, R, I: Iterator<Item = T>> {
-    iter: I,
-    response: R,
+pub(crate) struct IsomorphicHolder<T: PossiblyIsomorphic, R, I: Iterator<Item = T>> {
+    pub(crate) iter: I,
+    pub(crate) response: R,
 }
 
 /// An object which holds a response possibly coloured by it's isomorphisms
 /// If you just want to display it make sure `R`: [`ToString`] and use `ansify` on it
 #[derive(Clone, Debug)]
-struct ColouredObject<R: Response> {
-    value: R,
-    colour: Option<ColourKey>,
+pub(crate) struct ColouredObject<R: Response> {
+    pub(crate) value: R,
+    pub(crate) colour: Option<ColourKey>,
 }
 
 impl<R: Response + ToString> ColouredObject<R> {
@@ -157,7 +177,6 @@ impl<R: Response + ToString> ColouredObject<R> {
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 struct IsomorphSignature {
-    good: bool,
     signature: Vec<u8>,
 }
 
@@ -176,9 +195,14 @@ impl IsomorphSignature {
             }
         }
 
-        let good = signature.iter().collect::<HashSet<_>>().len() != signature.len();
+        IsomorphSignature { signature }
+    }
 
-        IsomorphSignature { signature, good }
+    /// Whether this signature has a repeated symbol, i.e. the word had two
+    /// positions mapped to each other by the isomorphism rather than each
+    /// position being its own class.
+    fn has_repeat(&self) -> bool {
+        self.signature.iter().collect::<HashSet<_>>().len() != self.signature.len()
     }
 }
 
@@ -186,6 +210,26 @@ struct IsomorphManager<T: PossiblyIsomorphic> {
     words: Vec<ColouredObject<T>>,
 }
 
+/// Configures which isomorph classes [`colour`] actually highlights.
+#[derive(Copy, Clone, Debug)]
+pub struct HighlightPolicy {
+    /// Only highlight a class once it has occurred at least this many times.
+    pub min_group: usize,
+    /// If set, also require the class's signature to contain a repeated
+    /// symbol (the original "must contain a duplicate letter" heuristic),
+    /// on top of `min_group`.
+    pub require_repeat: bool,
+}
+
+impl Default for HighlightPolicy {
+    fn default() -> Self {
+        Self {
+            min_group: 2,
+            require_repeat: false,
+        }
+    }
+}
+
 pub fn colour<
     T: PossiblyIsomorphic,
     R: Response,
@@ -193,6 +237,7 @@ pub fn colour<
     I: Iterator<Item = IsomorphicHolder<T, R, I2>>,
 >(
     words: I,
+    policy: HighlightPolicy,
 ) -> Vec<ColouredObject<R>> {
     let isomorphic_words = words
         .map(|word| {
@@ -203,61 +248,151 @@ pub fn colour<
         })
         .collect::<Vec<_>>();
 
-    let mut counters = HashMap::new();
-
-    isomorphic_words
-        .iter()
-        .for_each(|(_, e)| match counters.get_mut(e) {
-            Some(x) => *x = e.good,
-            None => {
-                counters.insert(e, false);
-            }
-        });
+    let mut occurrences: HashMap<&IsomorphSignature, usize> = HashMap::new();
+    for (_, e) in &isomorphic_words {
+        *occurrences.entry(e).or_insert(0) += 1;
+    }
 
     let mut colour_map: HashMap<IsomorphSignature, ColourKey> = HashMap::new();
 
     let mut colour = ColourKey::default();
     isomorphic_words
         .iter()
-        .map(|(s, e)| ColouredObject {
-            value: s.clone(),
-            colour: match counters.get(e) {
-                None => unreachable!(),
-                Some(false) => None,
-                Some(true) => Some({
-                    let col = match colour_map.get(e) {
-                        Some(c) => c.clone(),
-                        None => {
-                            let c = colour;
-                            colour = colour.next();
-                            colour_map.insert(e.clone(), c);
-                            c
-                        }
-                    };
-                    col
+        .map(|(s, e)| {
+            let qualifies = occurrences[e] >= policy.min_group
+                && (!policy.require_repeat || e.has_repeat());
+
+            ColouredObject {
+                value: s.clone(),
+                colour: qualifies.then(|| match colour_map.get(e) {
+                    Some(c) => *c,
+                    None => {
+                        let c = colour;
+                        colour = colour.next();
+                        colour_map.insert(e.clone(), c);
+                        c
+                    }
                 }),
-            },
+            }
         })
         .collect()
 }
 
+/// Number of distinct isomorph classes that were actually assigned a colour,
+/// i.e. the minimum palette size that won't wrap colours onto each other.
+fn distinct_classes<R: Response>(coloured: &[ColouredObject<R>]) -> usize {
+    coloured
+        .iter()
+        .filter_map(|e| e.colour)
+        .map(|c| c.idx)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn render<R: Response + ToString, C: Colour>(
+    coloured: &[ColouredObject<R>],
+    table: &[C],
+    separator: &str,
+) -> String {
+    coloured
+        .iter()
+        .map(|e| e.ansify(table) + RESET)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Renders `coloured` according to `args.format`/`args.palette`, joining
+/// tokens with `separator` (use `""` for a char stream, `" "` for words).
+fn output<R: Response + ToString>(
+    coloured: &[ColouredObject<R>],
+    args: &Args,
+    separator: &str,
+) -> String {
+    let table_len = distinct_classes(coloured).max(1);
+
+    match args.format {
+        cli::Format::Json => render::Json.render(coloured),
+        cli::Format::Html => render::Html {
+            table: &palette::generate_palette(table_len),
+            separator,
+        }
+        .render(coloured),
+        // Discord (8 SGR codes) and Ansi256 (216-colour cube) are the only
+        // backends whose palette actually runs out of room, so only they
+        // get their classes compacted via adjacency graph-colouring first —
+        // doing this for Truecolor/HTML/JSON would needlessly fold together
+        // unrelated classes that have nothing to do with each other.
+        cli::Format::Text => match args.palette {
+            Palette::Discord => {
+                let compact = palette::reassign_by_adjacency(coloured.to_vec());
+                let compact_len = distinct_classes(&compact).max(1);
+                render(
+                    &compact,
+                    &DiscordColourIterator::new(compact_len).collect::<Vec<_>>(),
+                    separator,
+                )
+            }
+            Palette::Truecolor => render(
+                coloured,
+                &palette::generate_palette(table_len),
+                separator,
+            ),
+            Palette::Ansi256 => {
+                let compact = palette::reassign_by_adjacency(coloured.to_vec());
+                let compact_len = distinct_classes(&compact).max(1);
+                render(
+                    &compact,
+                    &Ansi256ColourIterator::new(compact_len).collect::<Vec<_>>(),
+                    separator,
+                )
+            }
+        },
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut stdin = io::stdin();
-    let mut buf = String::new();
-    stdin.read_to_string(&mut buf)?;
-
-    // TODO: cli arg to swap
-    let table = DiscordColourIterator::new(1000).collect::<Vec<_>>(); //RGBIterator::new(1000).collect::<Vec<_>>();
-    let coloured = colour(buf.split(' ').map(|e| IsomorphicHolder {
-        iter: e.chars(),
-        response: e,
-    }))
-    .into_iter()
-    .map(|e| e.ansify(&table))
-    .map(|e| e + RESET)
-    .fold("".to_string(), |acc, e| acc + &e + " ");
-
-    println!("{coloured}");
+    let args = Args::parse();
+    let policy = HighlightPolicy {
+        min_group: args.min_group,
+        require_repeat: args.require_repeat,
+    };
+
+    let buf = match &args.input {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut stdin = io::stdin();
+            let mut buf = String::new();
+            stdin.read_to_string(&mut buf)?;
+            buf
+        }
+    };
+
+    if let Some(len) = args.window {
+        let chars = buf.chars().collect::<Vec<_>>();
+        if len == 0 || len > chars.len() {
+            return Err(format!(
+                "--window {len} must be between 1 and the input length ({})",
+                chars.len()
+            )
+            .into());
+        }
+
+        let coloured = window::colour_overlapping(&chars, len, policy);
+        println!("{}", output(&coloured, &args, ""));
+        return Ok(());
+    }
+
+    let tokens = cli::tokenize(&buf, &args);
+
+    let coloured = colour(
+        tokens.iter().map(|&e| IsomorphicHolder {
+            iter: e.chars(),
+            response: e,
+        }),
+        policy,
+    );
+
+    println!("{}", output(&coloured, &args, " "));
 
     Ok(())
 }
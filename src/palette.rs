@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{ColourKey, ColouredObject, Response, RGB};
+
+/// Golden-angle-seeded candidate hue/lightness pairs, generated as a pool
+/// for [`generate_palette`] to pick from rather than used directly in index
+/// order.
+fn candidates(count: usize) -> Vec<(f64, f64)> {
+    let golden_angle = (1.0 + f64::sqrt(5.0)) * 60.0;
+    (0..count)
+        .map(|i| {
+            let hue = (i as f64 * golden_angle) % 360.0;
+            // Cycle a few lightness bands so candidates don't all sit at the
+            // same brightness once hues start wrapping around.
+            let lightness = 0.4 + 0.15 * (i % 3) as f64;
+            (hue, lightness)
+        })
+        .collect()
+}
+
+/// Circular hue distance combined with a lightness term, in degrees.
+fn hsl_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let hue_diff = {
+        let d = (a.0 - b.0).abs() % 360.0;
+        d.min(360.0 - d)
+    };
+    let lightness_diff = (a.1 - b.1) * 360.0;
+    (hue_diff * hue_diff + lightness_diff * lightness_diff).sqrt()
+}
+
+/// Generates `n` colours spread as far apart as possible in HSL space.
+///
+/// A pool of golden-angle-seeded candidates is greedily reduced by
+/// farthest-point selection: each colour picked maximises its minimum
+/// distance to every colour already chosen, so even once `n` exceeds what a
+/// small fixed palette (e.g. Discord's 8 SGR colours) could offer, the
+/// result degrades gracefully instead of clumping.
+pub fn generate_palette(n: usize) -> Vec<RGB> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let pool = candidates(n * 8);
+    let mut chosen = vec![pool[0]];
+
+    while chosen.len() < n {
+        let next = pool
+            .iter()
+            .copied()
+            .max_by(|a, b| {
+                let min_dist = |p: (f64, f64)| {
+                    chosen
+                        .iter()
+                        .map(|&c| hsl_distance(p, c))
+                        .fold(f64::MAX, f64::min)
+                };
+                min_dist(*a)
+                    .partial_cmp(&min_dist(*b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .expect("pool is non-empty");
+        chosen.push(next);
+    }
+
+    chosen
+        .into_iter()
+        .map(|(h, l)| RGB::from_hsl(h, 0.9, l))
+        .collect()
+}
+
+/// Builds an adjacency graph over the isomorph classes that appear in
+/// `coloured` — two classes are adjacent if they appear next to each other
+/// in the stream — then greedily reassigns colour indices so adjacent
+/// classes never share a slot when avoidable: nodes are processed in
+/// descending degree, each taking the lowest index not already used by an
+/// assigned neighbour, so classes that end up sharing a slot are pushed
+/// apart spatially. `main` runs this pass only for the Discord and Ansi256
+/// backends; palettes from [`generate_palette`] are used without it.
+pub fn reassign_by_adjacency<R: Response>(
+    coloured: Vec<ColouredObject<R>>,
+) -> Vec<ColouredObject<R>> {
+    let mut adjacency: HashMap<usize, HashSet<usize>> = HashMap::new();
+    let mut prev: Option<usize> = None;
+    for e in &coloured {
+        match e.colour {
+            Some(c) => {
+                adjacency.entry(c.idx).or_default();
+                if let Some(p) = prev {
+                    if p != c.idx {
+                        adjacency.entry(p).or_default().insert(c.idx);
+                        adjacency.entry(c.idx).or_default().insert(p);
+                    }
+                }
+                prev = Some(c.idx);
+            }
+            None => prev = None,
+        }
+    }
+
+    let mut nodes: Vec<usize> = adjacency.keys().copied().collect();
+    // Break same-degree ties on the node id itself — `adjacency.keys()`
+    // iterates in randomized HashMap order, and without a stable tiebreak
+    // the same input could get a different (if still valid) assignment on
+    // every run.
+    nodes.sort_by_key(|n| (std::cmp::Reverse(adjacency[n].len()), *n));
+
+    let mut assigned: HashMap<usize, usize> = HashMap::new();
+    for node in nodes {
+        let used: HashSet<usize> = adjacency[&node]
+            .iter()
+            .filter_map(|n| assigned.get(n))
+            .copied()
+            .collect();
+        let new_idx = (0..).find(|i| !used.contains(i)).unwrap();
+        assigned.insert(node, new_idx);
+    }
+
+    coloured
+        .into_iter()
+        .map(|mut e| {
+            if let Some(c) = e.colour {
+                e.colour = Some(ColourKey {
+                    idx: assigned[&c.idx],
+                });
+            }
+            e
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coloured(classes: &[Option<usize>]) -> Vec<ColouredObject<usize>> {
+        classes
+            .iter()
+            .enumerate()
+            .map(|(i, class)| ColouredObject {
+                value: i,
+                colour: class.map(|idx| ColourKey { idx }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reassignment_is_deterministic_across_runs() {
+        let input = coloured(&[Some(0), Some(1), Some(0), Some(2), Some(1), Some(2), Some(0)]);
+        let first = reassign_by_adjacency(input.clone());
+        for _ in 0..20 {
+            let again = reassign_by_adjacency(input.clone());
+            assert_eq!(
+                first.iter().map(|e| e.colour).collect::<Vec<_>>(),
+                again.iter().map(|e| e.colour).collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    #[test]
+    fn adjacent_classes_never_share_a_slot() {
+        let input = coloured(&[Some(0), Some(1), Some(0), Some(1)]);
+        let result = reassign_by_adjacency(input);
+        assert_ne!(result[0].colour, result[1].colour);
+        assert_eq!(result[0].colour, result[2].colour);
+    }
+}
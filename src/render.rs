@@ -0,0 +1,101 @@
+use crate::{ColouredObject, Response, RGB};
+
+/// A way to turn a coloured run of tokens into a whole output document,
+/// parallel to how [`crate::Colour`] turns a single colour into an escape
+/// sequence. Unlike `Colour::ansify`, a renderer sees every token at once,
+/// since formats like JSON need to group tokens by isomorph class rather
+/// than decorate them one at a time.
+pub trait OutputFormat<R: Response + ToString> {
+    fn render(&self, coloured: &[ColouredObject<R>]) -> String;
+}
+
+/// Wraps every coloured token in a `<span style="color:#rrggbb">`, leaving
+/// uncoloured tokens bare. Takes its own `RGB` table since CSS colours have
+/// to be concrete hex values regardless of which [`crate::Colour`] backend
+/// the terminal output is using.
+pub struct Html<'a> {
+    pub table: &'a [RGB],
+    /// Inserted between rendered tokens. Use `""` when `R` is `char` (the
+    /// spacing is already part of the stream) and `" "` for word tokens.
+    pub separator: &'a str,
+}
+
+impl<'a, R: Response + ToString> OutputFormat<R> for Html<'a> {
+    fn render(&self, coloured: &[ColouredObject<R>]) -> String {
+        coloured
+            .iter()
+            .map(|e| match e.colour {
+                Some(colour) => {
+                    let rgb = colour.reify(self.table);
+                    format!(
+                        r#"<span style="color:#{:02x}{:02x}{:02x}">{}</span>"#,
+                        rgb.r,
+                        rgb.g,
+                        rgb.b,
+                        escape_html(&e.value.to_string())
+                    )
+                }
+                None => escape_html(&e.value.to_string()),
+            })
+            .collect::<Vec<_>>()
+            .join(self.separator)
+    }
+}
+
+/// Emits the isomorph grouping itself as the primary artifact: a JSON document
+/// `{"groups":[{"class":N,"members":[...]}, ...]}` listing each isomorph class
+/// with the responses that belong to it, in ascending class order.
+/// Uncoloured tokens (no class, or filtered out by `--min-group`) are omitted.
+pub struct Json;
+
+impl<R: Response + ToString> OutputFormat<R> for Json {
+    fn render(&self, coloured: &[ColouredObject<R>]) -> String {
+        let mut groups: std::collections::BTreeMap<usize, Vec<String>> =
+            std::collections::BTreeMap::new();
+        for e in coloured {
+            if let Some(colour) = e.colour {
+                groups
+                    .entry(colour.idx)
+                    .or_default()
+                    .push(e.value.to_string());
+            }
+        }
+
+        let body = groups
+            .into_iter()
+            .map(|(idx, members)| {
+                let members = members
+                    .iter()
+                    .map(|m| format!("\"{}\"", escape_json(m)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!("{{\"class\":{idx},\"members\":[{members}]}}")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("{{\"groups\":[{body}]}}")
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
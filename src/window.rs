@@ -0,0 +1,107 @@
+use std::ops::Range;
+
+use crate::{colour, ColourKey, ColouredObject, HighlightPolicy, IsomorphicHolder};
+
+/// Builds one [`IsomorphicHolder`] per window of `len` consecutive characters,
+/// sliding one character at a time across `chars`.
+///
+/// This is the `T = char`, `R = Range<usize>` case the [`IsomorphicHolder`]
+/// doc comment describes: the window's text is what gets checked for
+/// isomorphism, and the char-index range it came from (an index into
+/// `chars`, not a byte offset into the original `str`) is what gets
+/// coloured.
+pub fn windows(
+    chars: &[char],
+    len: usize,
+) -> impl Iterator<Item = IsomorphicHolder<char, Range<usize>, std::iter::Copied<std::slice::Iter<'_, char>>>>
+       + '_ {
+    let count = chars.len().saturating_sub(len) + 1;
+    (0..count).map(move |i| IsomorphicHolder {
+        iter: chars[i..i + len].iter().copied(),
+        response: i..i + len,
+    })
+}
+
+/// Runs the sliding-window isomorph search over `chars` for window length
+/// `len` and flattens the (possibly overlapping) coloured windows back down
+/// onto one colour per character.
+///
+/// Overlapping windows can disagree about a character's colour. We resolve
+/// that by picking the covering window whose class occurs most often overall,
+/// breaking ties by the earliest (lowest-index) class.
+pub fn colour_overlapping(
+    chars: &[char],
+    len: usize,
+    policy: HighlightPolicy,
+) -> Vec<ColouredObject<char>> {
+    if len == 0 || len > chars.len() {
+        return chars
+            .iter()
+            .map(|&value| ColouredObject {
+                value,
+                colour: None,
+            })
+            .collect();
+    }
+
+    let coloured_windows = colour(windows(chars, len), policy);
+
+    let mut frequency: std::collections::HashMap<ColourKey, usize> = std::collections::HashMap::new();
+    for window in &coloured_windows {
+        if let Some(c) = window.colour {
+            *frequency.entry(c).or_insert(0) += 1;
+        }
+    }
+
+    let mut covering: Vec<Vec<ColourKey>> = vec![Vec::new(); chars.len()];
+    for window in &coloured_windows {
+        if let Some(c) = window.colour {
+            for position in window.value.clone() {
+                covering[position].push(c);
+            }
+        }
+    }
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let colour = covering[i]
+                .iter()
+                .max_by_key(|c| (frequency[c], std::cmp::Reverse(c.idx)))
+                .copied();
+            ColouredObject { value, colour }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn window_larger_than_input_is_left_uncoloured() {
+        let input = chars("ab");
+        let result = colour_overlapping(&input, 5, HighlightPolicy::default());
+        assert!(result.iter().all(|e| e.colour.is_none()));
+    }
+
+    #[test]
+    fn overlapping_windows_of_the_same_class_colour_the_whole_span() {
+        // Windows at 0..2 ("aa") and 2..4 ("bb") share the signature [0, 0],
+        // so both get coloured; the middle window 1..3 ("ab") is signature
+        // [0, 1] and occurs only once, so it stays uncoloured and never
+        // overrides its neighbours' colour in the overlap.
+        let input = chars("aabb");
+        let result = colour_overlapping(&input, 2, HighlightPolicy::default());
+        let colours: Vec<_> = result.iter().map(|e| e.colour).collect();
+        assert!(colours.iter().all(Option::is_some));
+        assert_eq!(colours[0], colours[1]);
+        assert_eq!(colours[1], colours[2]);
+        assert_eq!(colours[2], colours[3]);
+    }
+}